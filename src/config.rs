@@ -0,0 +1,49 @@
+//! Multi-account configuration: `~/.config/llm-quota/config.toml`.
+//!
+//! Lets a user with several Claude/ChatGPT accounts describe each one as a
+//! named `[profile.<name>]` table instead of juggling environment variables
+//! and the fixed `~/.claude` / `~/.codex` paths.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Claude,
+    Codex,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Profile {
+    pub provider: Provider,
+    pub credentials_path: Option<PathBuf>,
+    pub base_url: Option<String>,
+    pub account_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Config {
+    #[serde(default, rename = "profile")]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// `~/.config/llm-quota/config.toml`, unless overridden with `--config`.
+pub fn default_path() -> PathBuf {
+    PathBuf::from(env::var("HOME").unwrap_or_else(|_| "/tmp".to_string()))
+        .join(".config/llm-quota/config.toml")
+}
+
+/// Load `path`. Missing files are treated as an empty config so `--profile`
+/// and `--all` are opt-in rather than requiring the file to exist.
+pub fn load(path: &Path) -> Result<Config, String> {
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    toml::from_str(&content).map_err(|e| format!("failed to parse {}: {e}", path.display()))
+}
@@ -1,22 +1,59 @@
 use clap::{Parser, Subcommand};
-use opentelemetry::trace::{Span, TraceContextExt, Tracer, get_active_span};
-use opentelemetry::{Context, KeyValue, global};
-use opentelemetry_sdk::Resource;
+use opentelemetry::global;
 use opentelemetry_sdk::trace::SdkTracerProvider;
-use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
-use serde::{Deserialize, Serialize};
-use serde_json::{Map, Value, json};
+use serde::Serialize;
+use serde_json::{Value, json};
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod config;
+mod metrics;
+mod output;
+mod provider;
+mod providers;
+mod retry;
+
+/// Refresh proactively once a token is within this many milliseconds of expiring.
+const REFRESH_SKEW_MS: i64 = 60_000;
 
 #[derive(Parser, Debug)]
 #[command(name = "llm-quota")]
 #[command(about = "Usage limit utilities", long_about = None)]
 struct Cli {
+    /// Required unless --profile or --all is given
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
+
+    /// Skip the automatic OAuth token refresh and use the stored access token as-is
+    #[arg(long, global = true)]
+    no_refresh: bool,
+
+    /// Path to the multi-account config file [default: ~/.config/llm-quota/config.toml]
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Use the named profile from the config file instead of environment/default paths
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Query every profile in the config file, tagging each result with its profile name
+    #[arg(long, global = true)]
+    all: bool,
+
+    /// Maximum number of retry attempts for connection errors and 429/500/502/503/504 responses
+    #[arg(long, global = true, default_value_t = 5)]
+    max_retries: u32,
+
+    /// Base delay in milliseconds for the exponential backoff between retries
+    #[arg(long, global = true, default_value_t = 500)]
+    retry_base_ms: u64,
+
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value = "json")]
+    format: output::Format,
 }
 
 #[derive(Subcommand, Debug)]
@@ -29,7 +66,13 @@ Environment Variables:
 
   OTEL_EXPORTER_OTLP_ENDPOINT  OTLP endpoint (enables tracing when set)
   OTEL_EXPORTER_OTLP_PROTOCOL  http/protobuf or grpc [default: grpc]
-  OTEL_EXPORTER_OTLP_HEADERS   Auth headers (e.g. Authorization=Basic ...)")]
+  OTEL_EXPORTER_OTLP_HEADERS   Auth headers (e.g. Authorization=Basic ...)
+
+The stored access token is refreshed automatically once it is within 60s of
+expiring; pass --no-refresh to disable this and use the stored token as-is.
+
+Transient failures (connection errors, 429/500/502/503/504) are retried with
+exponential backoff; see --max-retries and --retry-base-ms.")]
     Claude,
     /// Fetch Codex usage limits and print JSON output
     #[command(after_help = "\
@@ -41,44 +84,58 @@ Environment Variables:
 
   OTEL_EXPORTER_OTLP_ENDPOINT  OTLP endpoint (enables tracing when set)
   OTEL_EXPORTER_OTLP_PROTOCOL  http/protobuf or grpc [default: grpc]
-  OTEL_EXPORTER_OTLP_HEADERS   Auth headers (e.g. Authorization=Basic ...)")]
-    Codex,
-}
+  OTEL_EXPORTER_OTLP_HEADERS   Auth headers (e.g. Authorization=Basic ...)
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-struct UsageWindow {
-    utilization: Option<f64>,
-    resets_at: Option<String>,
-}
+The stored access token is refreshed automatically once it is within 60s of
+expiring; pass --no-refresh to disable this and use the stored token as-is.
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-struct OAuthUsageResponse {
-    five_hour: Option<UsageWindow>,
-    seven_day: Option<UsageWindow>,
-    seven_day_sonnet: Option<UsageWindow>,
+Transient failures (connection errors, 429/500/502/503/504) are retried with
+exponential backoff; see --max-retries and --retry-base-ms.")]
+    Codex,
 }
 
-#[derive(Debug, Deserialize)]
-struct CodexAuthFile {
-    tokens: Option<CodexTokens>,
+impl Commands {
+    fn provider(&self) -> config::Provider {
+        match self {
+            Commands::Claude => config::Provider::Claude,
+            Commands::Codex => config::Provider::Codex,
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct CodexTokens {
-    access_token: Option<String>,
-    account_id: Option<String>,
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
 }
 
-#[derive(Debug, Deserialize)]
-struct ClaudeCredentialsFile {
-    #[serde(rename = "claudeAiOauth")]
-    claude_ai_oauth: Option<ClaudeAiOauth>,
+fn is_expiring_soon(expires_at_ms: i64) -> bool {
+    expires_at_ms - now_ms() < REFRESH_SKEW_MS
 }
 
-#[derive(Debug, Deserialize)]
-struct ClaudeAiOauth {
-    #[serde(rename = "accessToken")]
-    access_token: Option<String>,
+/// Write `value` to `path` atomically (temp file + rename) so a concurrent
+/// reader never observes a partially-written credentials file. The original
+/// file's permissions (typically 0600, since these files hold bearer tokens)
+/// are carried over to the temp file before the rename, rather than letting
+/// it pick up the process umask.
+fn write_json_atomic(path: &Path, value: &impl Serialize) -> Result<(), String> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let tmp_path = dir.join(format!(
+        ".{}.tmp",
+        path.file_name().and_then(|n| n.to_str()).unwrap_or("credentials.json")
+    ));
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("failed to serialize {}: {e}", path.display()))?;
+    fs::write(&tmp_path, &content)
+        .map_err(|e| format!("failed to write {}: {e}", tmp_path.display()))?;
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(&tmp_path, metadata.permissions())
+            .map_err(|e| format!("failed to set permissions on {}: {e}", tmp_path.display()))?;
+    }
+    fs::rename(&tmp_path, path)
+        .map_err(|e| format!("failed to replace {}: {e}", path.display()))?;
+    Ok(())
 }
 
 fn init_tracer_provider() -> Option<SdkTracerProvider> {
@@ -88,7 +145,7 @@ fn init_tracer_provider() -> Option<SdkTracerProvider> {
     Some(
         SdkTracerProvider::builder()
             .with_resource(
-                Resource::builder()
+                opentelemetry_sdk::Resource::builder()
                     .with_service_name("llm-quota")
                     .build(),
             )
@@ -110,359 +167,194 @@ fn init_tracer_provider() -> Option<SdkTracerProvider> {
     )
 }
 
-fn left(v: Option<f64>) -> Option<f64> {
-    v.map(|n| (100.0 - n).max(0.0))
+fn print_result(format: output::Format, value: &Value) {
+    println!("{}", output::render(format, value));
 }
 
-fn print_json(value: &Value) {
-    match serde_json::to_string_pretty(value) {
-        Ok(s) => println!("{s}"),
-        Err(_) => println!("{{\"ok\":false,\"error\":\"failed to serialize output\"}}"),
+#[tokio::main]
+async fn main() -> ExitCode {
+    let provider = init_tracer_provider();
+    if let Some(ref p) = provider {
+        global::set_tracer_provider(p.clone());
     }
-}
-
-fn read_claude_oauth_token() -> Result<String, String> {
-    if let Ok(v) = env::var("ANTHROPIC_OAUTH_API_KEY") {
-        let token = v.trim().to_string();
-        if !token.is_empty() {
-            return Ok(token);
-        }
+    let meter_provider = metrics::init_meter_provider();
+    if let Some(ref p) = meter_provider {
+        global::set_meter_provider(p.clone());
     }
-
-    let credentials_path =
-        PathBuf::from(env::var("HOME").unwrap_or_else(|_| "/tmp".to_string()))
-            .join(".claude/.credentials.json");
-
-    let content = fs::read_to_string(&credentials_path)
-        .map_err(|e| format!("failed to read {}: {e}", credentials_path.display()))?;
-    let credentials: ClaudeCredentialsFile = serde_json::from_str(&content)
-        .map_err(|e| format!("failed to parse {}: {e}", credentials_path.display()))?;
-
-    credentials
-        .claude_ai_oauth
-        .and_then(|o| o.access_token)
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .ok_or_else(|| {
-            "ANTHROPIC_OAUTH_API_KEY is not set and accessToken was not found in ~/.claude/.credentials.json"
-                .to_string()
-        })
+    let cli = Cli::parse();
+    let exit_code = dispatch(&cli).await;
+    if let Some(p) = meter_provider {
+        let _ = p.shutdown();
+    }
+    if let Some(p) = provider {
+        let _ = p.shutdown();
+    }
+    exit_code
 }
 
-async fn run_claude() -> ExitCode {
-    let tracer = global::tracer("llm-quota");
-    let _root_guard = Context::current_with_span(tracer.start("run_claude")).attach();
-
-    let base_url =
-        env::var("ANTHROPIC_BASE_URL").unwrap_or_else(|_| "https://api.anthropic.com".to_string());
+async fn dispatch(cli: &Cli) -> ExitCode {
+    let retry_config = retry::RetryConfig {
+        max_retries: cli.max_retries,
+        base_delay: std::time::Duration::from_millis(cli.retry_base_ms),
+    };
 
-    let api_key = {
-        let _auth_guard =
-            Context::current_with_span(tracer.start("resolve_auth")).attach();
-        match read_claude_oauth_token() {
-            Ok(v) => v,
+    // Only touch the multi-account config file when something actually needs
+    // it, so an unrelated or pre-existing broken config.toml doesn't break
+    // the plain `llm-quota claude`/`codex` path.
+    let needs_config = cli.all || cli.profile.is_some() || cli.config.is_some();
+    let cfg = if needs_config {
+        let config_path = cli.config.clone().unwrap_or_else(config::default_path);
+        match config::load(&config_path) {
+            Ok(c) => Some((c, config_path)),
             Err(e) => {
-                print_json(&json!({"ok": false, "error": e}));
+                print_result(cli.format, &json!({"ok": false, "error": e}));
                 return ExitCode::from(2);
             }
         }
+    } else {
+        None
     };
 
-    let url = format!("{}/api/oauth/usage", base_url.trim_end_matches('/'));
-
-    let client = match reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-    {
-        Ok(c) => c,
-        Err(e) => {
-            print_json(&json!({"ok": false, "error": format!("failed to build HTTP client: {e}")}));
-            return ExitCode::from(1);
-        }
-    };
-
-    let mut http_span = tracer.start("http_request");
-    http_span.set_attribute(KeyValue::new("http.request.method", "GET"));
-    http_span.set_attribute(KeyValue::new("url.full", url.clone()));
-
-    let response = match client
-        .get(url)
-        .header(ACCEPT, "application/json, text/plain, */*")
-        .header(CONTENT_TYPE, "application/json")
-        .header(USER_AGENT, "claude-code/2.0.32")
-        .header(AUTHORIZATION, format!("Bearer {api_key}"))
-        .header("anthropic-beta", "oauth-2025-04-20")
-        .send()
-        .await
-    {
-        Ok(r) => r,
-        Err(e) => {
-            print_json(&json!({"ok": false, "error": format!("request failed: {e}")}));
-            return ExitCode::from(1);
+    if cli.all {
+        if cli.profile.is_some() {
+            print_result(
+                cli.format,
+                &json!({
+                    "ok": false,
+                    "error": "--all cannot be combined with --profile, which queries a single named profile",
+                }),
+            );
+            return ExitCode::from(2);
         }
-    };
-
-    let status = response.status();
-    http_span.set_attribute(KeyValue::new(
-        "http.response.status_code",
-        status.as_u16() as i64,
-    ));
-    let body_text = match response.text().await {
-        Ok(t) => t,
-        Err(e) => {
-            print_json(
-                &json!({"ok": false, "error": format!("failed to read response body: {e}")}),
+        if let Some(cmd) = &cli.command {
+            print_result(
+                cli.format,
+                &json!({
+                    "ok": false,
+                    "error": format!(
+                        "a provider subcommand ({cmd:?}) cannot be combined with --all, which queries every configured profile",
+                    ),
+                }),
             );
-            return ExitCode::from(1);
+            return ExitCode::from(2);
         }
-    };
-    http_span.add_event(
-        "http.response.body",
-        vec![KeyValue::new("body", body_text.clone())],
-    );
-    drop(http_span);
-
-    if !status.is_success() {
-        print_json(&json!({
-            "ok": false,
-            "error": format!("HTTP {}", status.as_u16()),
-            "response_body": body_text,
-        }));
-        return ExitCode::from(1);
+        let (cfg, _) = cfg.expect("config is loaded whenever cli.all is set");
+        return run_all_profiles(&cfg, cli.no_refresh, &retry_config, cli.format).await;
     }
 
-    let usage_value: Value = match serde_json::from_str(&body_text) {
-        Ok(v) => v,
-        Err(e) => {
-            print_json(&json!({"ok": false, "error": format!("failed to parse JSON: {e}")}));
-            return ExitCode::from(1);
-        }
-    };
-
-    let usage: OAuthUsageResponse =
-        serde_json::from_value(usage_value.clone()).unwrap_or(OAuthUsageResponse {
-            five_hour: None,
-            seven_day: None,
-            seven_day_sonnet: None,
-        });
-
-    let mut summary: Map<String, Value> = Map::new();
-    summary.insert(
-        "five_hour".to_string(),
-        json!({
-            "resets_at": usage.five_hour.as_ref().and_then(|w| w.resets_at.clone()),
-            "percent_left": left(usage.five_hour.as_ref().and_then(|w| w.utilization)),
-        }),
-    );
-    summary.insert(
-        "seven_day".to_string(),
-        json!({
-            "resets_at": usage.seven_day.as_ref().and_then(|w| w.resets_at.clone()),
-            "percent_left": left(usage.seven_day.as_ref().and_then(|w| w.utilization)),
-        }),
-    );
-    summary.insert(
-        "seven_day_sonnet".to_string(),
-        json!({
-            "resets_at": usage.seven_day_sonnet.as_ref().and_then(|w| w.resets_at.clone()),
-            "percent_left": left(usage.seven_day_sonnet.as_ref().and_then(|w| w.utilization)),
-        }),
-    );
-
-    let out = json!({
-        "ok": true,
-        "usage": usage_value,
-        "summary": summary,
-    });
-    get_active_span(|span| {
-        span.add_event(
-            "output",
-            vec![KeyValue::new(
-                "json",
-                serde_json::to_string(&out).unwrap_or_default(),
-            )],
-        );
-    });
-
-    print_json(&out);
-    ExitCode::SUCCESS
-}
-
-fn read_codex_auth() -> Result<(String, String), String> {
-    if let Ok(access_token) = env::var("OPENAI_OAUTH_API_KEY") {
-        let access_token = access_token.trim().to_string();
-        if !access_token.is_empty() {
-            let account_id = env::var("OPENAI_ACCOUNT_ID")
-                .or_else(|_| env::var("CHATGPT_ACCOUNT_ID"))
-                .map_err(|_| {
-                    "OPENAI_OAUTH_API_KEY is set, but OPENAI_ACCOUNT_ID or CHATGPT_ACCOUNT_ID is missing"
-                        .to_string()
-                })?;
-            let account_id = account_id.trim().to_string();
-            if account_id.is_empty() {
-                return Err(
-                    "OPENAI_OAUTH_API_KEY is set, but OPENAI_ACCOUNT_ID/CHATGPT_ACCOUNT_ID is empty"
-                        .to_string(),
+    if let Some(name) = cli.profile.as_deref() {
+        let (cfg, config_path) = cfg.expect("config is loaded whenever cli.profile is set");
+        let profile = match cfg.profiles.get(name) {
+            Some(p) => p,
+            None => {
+                print_result(
+                    cli.format,
+                    &json!({
+                        "ok": false,
+                        "error": format!("profile '{name}' was not found in {}", config_path.display()),
+                    }),
                 );
+                return ExitCode::from(2);
             }
-            return Ok((access_token, account_id));
-        }
-    }
-
-    let auth_path = PathBuf::from(env::var("HOME").unwrap_or_else(|_| "/tmp".to_string()))
-        .join(".codex/auth.json");
-
-    let content = fs::read_to_string(&auth_path)
-        .map_err(|e| format!("failed to read auth file {}: {e}", auth_path.display()))?;
-    let auth: CodexAuthFile = serde_json::from_str(&content)
-        .map_err(|e| format!("failed to parse auth file {}: {e}", auth_path.display()))?;
-
-    let tokens = auth
-        .tokens
-        .ok_or_else(|| "tokens are missing in codex auth file".to_string())?;
-    let access_token = tokens
-        .access_token
-        .filter(|s| !s.trim().is_empty())
-        .ok_or_else(|| "access_token is missing in codex auth file".to_string())?;
-    let account_id = tokens
-        .account_id
-        .filter(|s| !s.trim().is_empty())
-        .ok_or_else(|| "account_id is missing in codex auth file".to_string())?;
-
-    Ok((access_token, account_id))
-}
-
-async fn run_codex() -> ExitCode {
-    let tracer = global::tracer("llm-quota");
-    let _root_guard = Context::current_with_span(tracer.start("run_codex")).attach();
-
-    let (access_token, account_id) = {
-        let _auth_guard =
-            Context::current_with_span(tracer.start("resolve_auth")).attach();
-        match read_codex_auth() {
-            Ok(v) => v,
-            Err(e) => {
-                print_json(&json!({"ok": false, "error": e}));
+        };
+        if let Some(cmd) = &cli.command {
+            if cmd.provider() != profile.provider {
+                print_result(
+                    cli.format,
+                    &json!({
+                        "ok": false,
+                        "error": format!(
+                            "profile '{name}' is a {:?} profile, which disagrees with the {cmd:?} subcommand",
+                            profile.provider,
+                        ),
+                    }),
+                );
                 return ExitCode::from(2);
             }
         }
-    };
+        let (exit_code, out) = fetch_profile(profile, Some(name), cli.no_refresh, &retry_config).await;
+        print_result(cli.format, &out);
+        return exit_code;
+    }
 
-    let base_url = env::var("CHATGPT_BASE_URL").unwrap_or_else(|_| "https://chatgpt.com".to_string());
-    let url = format!("{}/backend-api/wham/usage", base_url.trim_end_matches('/'));
-
-    let client = match reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-    {
-        Ok(c) => c,
-        Err(e) => {
-            print_json(&json!({"ok": false, "error": format!("failed to build HTTP client: {e}")}));
-            return ExitCode::from(1);
+    match &cli.command {
+        Some(Commands::Claude) => run_claude(cli.no_refresh, &retry_config, cli.format).await,
+        Some(Commands::Codex) => run_codex(cli.no_refresh, &retry_config, cli.format).await,
+        None => {
+            print_result(
+                cli.format,
+                &json!({
+                    "ok": false,
+                    "error": "a provider subcommand (claude|codex) is required unless --profile or --all is given",
+                }),
+            );
+            ExitCode::from(2)
         }
-    };
+    }
+}
 
-    let mut http_span = tracer.start("http_request");
-    http_span.set_attribute(KeyValue::new("http.request.method", "GET"));
-    http_span.set_attribute(KeyValue::new("url.full", url.clone()));
-
-    let response = match client
-        .get(url)
-        .header(ACCEPT, "application/json")
-        .header(CONTENT_TYPE, "application/json")
-        .header(USER_AGENT, "codex-cli")
-        .header(AUTHORIZATION, format!("Bearer {access_token}"))
-        .header("ChatGPT-Account-Id", account_id)
-        .send()
-        .await
-    {
-        Ok(r) => r,
-        Err(e) => {
-            print_json(&json!({"ok": false, "error": format!("request failed: {e}")}));
-            return ExitCode::from(1);
+async fn fetch_profile(
+    profile: &config::Profile,
+    profile_name: Option<&str>,
+    no_refresh: bool,
+    retry_config: &retry::RetryConfig,
+) -> (ExitCode, Value) {
+    match profile.provider {
+        config::Provider::Claude => {
+            let p = providers::claude::ClaudeProvider::new(no_refresh, Some(profile));
+            provider::fetch_quota(&p, profile_name, retry_config).await
         }
-    };
-
-    let status = response.status();
-    http_span.set_attribute(KeyValue::new(
-        "http.response.status_code",
-        status.as_u16() as i64,
-    ));
-    let body_text = match response.text().await {
-        Ok(t) => t,
-        Err(e) => {
-            print_json(&json!({"ok": false, "error": format!("failed to read response body: {e}")}));
-            return ExitCode::from(1);
+        config::Provider::Codex => {
+            let p = providers::codex::CodexProvider::new(no_refresh, Some(profile));
+            provider::fetch_quota(&p, profile_name, retry_config).await
         }
-    };
-    http_span.add_event(
-        "http.response.body",
-        vec![KeyValue::new("body", body_text.clone())],
-    );
-    drop(http_span);
-
-    if !status.is_success() {
-        print_json(&json!({
-            "ok": false,
-            "error": format!("HTTP {}", status.as_u16()),
-            "response_body": body_text,
-        }));
-        return ExitCode::from(1);
     }
+}
 
-    let usage_value: Value = match serde_json::from_str(&body_text) {
-        Ok(v) => v,
-        Err(e) => {
-            print_json(&json!({"ok": false, "error": format!("failed to parse JSON: {e}")}));
-            return ExitCode::from(1);
-        }
-    };
+async fn run_claude(
+    no_refresh: bool,
+    retry_config: &retry::RetryConfig,
+    format: output::Format,
+) -> ExitCode {
+    let p = providers::claude::ClaudeProvider::new(no_refresh, None);
+    let (exit_code, out) = provider::fetch_quota(&p, None, retry_config).await;
+    print_result(format, &out);
+    exit_code
+}
 
-    let primary = usage_value
-        .get("rate_limit")
-        .and_then(|v| v.get("primary_window"))
-        .cloned()
-        .unwrap_or(Value::Null);
-    let secondary = usage_value
-        .get("rate_limit")
-        .and_then(|v| v.get("secondary_window"))
-        .cloned()
-        .unwrap_or(Value::Null);
-
-    let out = json!({
-        "ok": true,
-        "usage": usage_value,
-        "summary": {
-            "five_hour": primary,
-            "seven_day": secondary
-        }
-    });
-    get_active_span(|span| {
-        span.add_event(
-            "output",
-            vec![KeyValue::new(
-                "json",
-                serde_json::to_string(&out).unwrap_or_default(),
-            )],
-        );
-    });
-
-    print_json(&out);
-    ExitCode::SUCCESS
+async fn run_codex(
+    no_refresh: bool,
+    retry_config: &retry::RetryConfig,
+    format: output::Format,
+) -> ExitCode {
+    let p = providers::codex::CodexProvider::new(no_refresh, None);
+    let (exit_code, out) = provider::fetch_quota(&p, None, retry_config).await;
+    print_result(format, &out);
+    exit_code
 }
 
-#[tokio::main]
-async fn main() -> ExitCode {
-    let provider = init_tracer_provider();
-    if let Some(ref p) = provider {
-        global::set_tracer_provider(p.clone());
+/// Query every profile in the config file and print the results, each
+/// tagged with its profile name.
+async fn run_all_profiles(
+    cfg: &config::Config,
+    no_refresh: bool,
+    retry_config: &retry::RetryConfig,
+    format: output::Format,
+) -> ExitCode {
+    let mut all_ok = true;
+    let mut results = Vec::with_capacity(cfg.profiles.len());
+    for (name, profile) in &cfg.profiles {
+        let (_exit_code, mut out) = fetch_profile(profile, Some(name), no_refresh, retry_config).await;
+        all_ok &= out.get("ok").and_then(Value::as_bool).unwrap_or(false);
+        if let Some(obj) = out.as_object_mut() {
+            obj.insert("profile".to_string(), json!(name));
+        }
+        results.push(out);
     }
-    let exit_code = match Cli::parse().command {
-        Commands::Claude => run_claude().await,
-        Commands::Codex => run_codex().await,
-    };
-    if let Some(p) = provider {
-        let _ = p.shutdown();
+    print_result(format, &json!(results));
+    if all_ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
     }
-    exit_code
 }
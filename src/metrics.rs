@@ -0,0 +1,78 @@
+//! OTLP metrics pipeline, parallel to the tracing pipeline in `main`.
+//!
+//! Spans carry the raw numbers as events, which is awkward to alert on, so
+//! each fetch also reports `llm_quota.utilization` / `llm_quota.percent_left`
+//! gauges tagged with `provider` and `window`.
+
+use opentelemetry::{KeyValue, global};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use serde_json::{Map, Value};
+use std::env;
+
+pub fn init_meter_provider() -> Option<SdkMeterProvider> {
+    if env::var("OTEL_EXPORTER_OTLP_ENDPOINT").is_err() {
+        return None;
+    }
+    Some(
+        SdkMeterProvider::builder()
+            .with_resource(
+                Resource::builder()
+                    .with_service_name("llm-quota")
+                    .build(),
+            )
+            .with_periodic_reader(match env::var("OTEL_EXPORTER_OTLP_PROTOCOL").as_deref() {
+                Ok("http/protobuf") | Ok("http/json") => std::thread::spawn(|| {
+                    opentelemetry_otlp::MetricExporter::builder()
+                        .with_http()
+                        .build()
+                })
+                .join()
+                .ok()?
+                .ok()?,
+                _ => opentelemetry_otlp::MetricExporter::builder()
+                    .with_tonic()
+                    .build()
+                    .ok()?,
+            })
+            .build(),
+    )
+}
+
+/// Derive (utilization, percent_left) from a summary window, whichever of
+/// `percent_left` (Claude) or `used_percent` (Codex) shape it carries.
+///
+/// Shared with [`crate::output`], which only needs the `percent_left` half.
+pub(crate) fn quota_observation(window: &Value) -> Option<(f64, f64)> {
+    if let Some(percent_left) = window.get("percent_left").and_then(Value::as_f64) {
+        return Some(((100.0 - percent_left).max(0.0), percent_left));
+    }
+    if let Some(used_percent) = window.get("used_percent").and_then(Value::as_f64) {
+        return Some((used_percent, (100.0 - used_percent).max(0.0)));
+    }
+    None
+}
+
+/// Record `llm_quota.utilization` / `llm_quota.percent_left` gauges for each
+/// window in `summary`, tagged with `provider`, `window`, and (under
+/// `--profile`/`--all`) `profile` — without it, two same-provider profiles
+/// report to the identical series and per-account alerting is impossible.
+pub fn record_quota_metrics(provider: &str, profile: Option<&str>, summary: &Map<String, Value>) {
+    let meter = global::meter("llm-quota");
+    let utilization = meter.f64_gauge("llm_quota.utilization").build();
+    let percent_left = meter.f64_gauge("llm_quota.percent_left").build();
+    for (window, value) in summary {
+        let Some((utilization_value, percent_left_value)) = quota_observation(value) else {
+            continue;
+        };
+        let mut attrs = vec![
+            KeyValue::new("provider", provider.to_string()),
+            KeyValue::new("window", window.clone()),
+        ];
+        if let Some(profile) = profile {
+            attrs.push(KeyValue::new("profile", profile.to_string()));
+        }
+        utilization.record(utilization_value, &attrs);
+        percent_left.record(percent_left_value, &attrs);
+    }
+}
@@ -0,0 +1,105 @@
+//! Alternate renderings of the fetch envelope: a human-readable table and a
+//! Prometheus textfile exposition, for users who scrape/collect locally
+//! instead of pushing to the OTLP endpoint in [`crate::metrics`].
+
+use crate::metrics::quota_observation;
+use clap::ValueEnum;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Pretty-printed JSON envelope
+    Json,
+    /// Aligned human-readable table of percent_left/resets_at per window
+    Table,
+    /// Prometheus textfile exposition format
+    Prometheus,
+}
+
+/// `value` is either a single fetch envelope or, in `--all` mode, a JSON
+/// array of envelopes each tagged with `"profile"`.
+pub fn render(format: Format, value: &Value) -> String {
+    match format {
+        Format::Json => serde_json::to_string_pretty(value)
+            .unwrap_or_else(|_| "{\"ok\":false,\"error\":\"failed to serialize output\"}".to_string()),
+        Format::Table => render_table(value),
+        Format::Prometheus => render_prometheus(value),
+    }
+}
+
+fn envelopes(value: &Value) -> Vec<&Value> {
+    match value.as_array() {
+        Some(items) => items.iter().collect(),
+        None => vec![value],
+    }
+}
+
+fn label(envelope: &Value) -> String {
+    let provider = envelope.get("provider").and_then(Value::as_str).unwrap_or("unknown");
+    match envelope.get("profile").and_then(Value::as_str) {
+        Some(profile) => format!("{provider}/{profile}"),
+        None => provider.to_string(),
+    }
+}
+
+fn percent_left(window: &Value) -> Option<f64> {
+    quota_observation(window).map(|(_utilization, percent_left)| percent_left)
+}
+
+fn render_table(value: &Value) -> String {
+    let mut lines = Vec::new();
+    for envelope in envelopes(value) {
+        let label = label(envelope);
+        if !envelope.get("ok").and_then(Value::as_bool).unwrap_or(false) {
+            let error = envelope
+                .get("error")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error");
+            lines.push(format!("{label:<20} ERROR  {error}"));
+            continue;
+        }
+        let Some(summary) = envelope.get("summary").and_then(Value::as_object) else {
+            continue;
+        };
+        for (window, window_value) in summary {
+            let percent = percent_left(window_value)
+                .map(|p| format!("{p:.1}%"))
+                .unwrap_or_else(|| "-".to_string());
+            let resets_at = window_value
+                .get("resets_at")
+                .and_then(Value::as_str)
+                .unwrap_or("-");
+            lines.push(format!(
+                "{label:<20} {window:<16} {percent:>7} left  resets {resets_at}"
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+fn render_prometheus(value: &Value) -> String {
+    let mut out = String::new();
+    out.push_str("# HELP llm_quota_percent_left Percent of the quota window remaining.\n");
+    out.push_str("# TYPE llm_quota_percent_left gauge\n");
+    for envelope in envelopes(value) {
+        if !envelope.get("ok").and_then(Value::as_bool).unwrap_or(false) {
+            continue;
+        }
+        let provider = envelope.get("provider").and_then(Value::as_str).unwrap_or("unknown");
+        let profile = envelope.get("profile").and_then(Value::as_str);
+        let Some(summary) = envelope.get("summary").and_then(Value::as_object) else {
+            continue;
+        };
+        for (window, window_value) in summary {
+            if let Some(percent) = percent_left(window_value) {
+                let profile_label = profile
+                    .map(|p| format!(",profile=\"{p}\""))
+                    .unwrap_or_default();
+                out.push_str(&format!(
+                    "llm_quota_percent_left{{provider=\"{provider}\",window=\"{window}\"{profile_label}}} {percent}\n"
+                ));
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,180 @@
+//! `QuotaProvider` abstracts the shared shape of "fetch a quota usage
+//! endpoint and summarize it" so adding a new backend (Gemini, a
+//! self-hosted endpoint, ...) only means implementing this trait, not
+//! copy-pasting client construction, tracing, retry, and envelope code.
+
+use crate::metrics;
+use crate::retry::{self, RetryConfig};
+use opentelemetry::trace::{Span, TraceContextExt, Tracer, get_active_span};
+use opentelemetry::{Context, KeyValue, global};
+use reqwest::header::HeaderMap;
+use serde_json::{Map, Value, json};
+use std::future::Future;
+use std::process::ExitCode;
+use std::time::Duration;
+
+/// Request timeout shared by the quota fetch and the OAuth token refresh, so
+/// a slow-loris token endpoint can't hang the whole invocation.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Resolved credentials for a single fetch, opaque to `fetch_quota` itself.
+pub struct AuthHeaders {
+    pub access_token: String,
+    pub account_id: Option<String>,
+}
+
+/// Build an HTTP client with [`REQUEST_TIMEOUT`]. Shared by `fetch_quota` and
+/// each provider's OAuth refresh call.
+pub(crate) fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("failed to build HTTP client: {e}"))
+}
+
+pub trait QuotaProvider {
+    /// Short, lowercase name used for span/metric attribution (e.g. "claude").
+    fn name(&self) -> &'static str;
+
+    /// Resolve an access token (and account id, if the provider needs one),
+    /// refreshing it first if it is expiring and refresh is enabled.
+    fn auth(&self) -> impl Future<Output = Result<AuthHeaders, String>> + Send;
+
+    /// The configured or default base URL for this provider.
+    fn base_url(&self) -> String;
+
+    /// Build the full request URL from the base URL.
+    fn request_url(&self, base_url: &str) -> String;
+
+    /// Build the request headers, given the resolved auth. Fails if a header
+    /// value (e.g. a corrupted access token) isn't valid header bytes.
+    fn headers(&self, auth: &AuthHeaders) -> Result<HeaderMap, String>;
+
+    /// Shape the raw usage JSON into the `{"five_hour": ..., ...}` summary.
+    fn summarize(&self, usage: &Value) -> Map<String, Value>;
+}
+
+/// Owns the timeout, tracing spans, retry, status/body error handling, and
+/// the `{"ok":..,"usage":..,"summary":..}` envelope shared by every provider.
+/// `profile`, when set, is attached to the recorded metrics so per-account
+/// gauges don't collide under `--all`/`--profile`.
+pub async fn fetch_quota<P: QuotaProvider>(
+    provider: &P,
+    profile: Option<&str>,
+    retry_config: &RetryConfig,
+) -> (ExitCode, Value) {
+    let tracer = global::tracer("llm-quota");
+    let _root_guard =
+        Context::current_with_span(tracer.start(format!("run_{}", provider.name()))).attach();
+
+    let auth = {
+        let _auth_guard = Context::current_with_span(tracer.start("resolve_auth")).attach();
+        match provider.auth().await {
+            Ok(a) => a,
+            Err(e) => return (ExitCode::from(2), json!({"ok": false, "provider": provider.name(), "error": e})),
+        }
+    };
+
+    let base_url = provider.base_url();
+    let url = provider.request_url(&base_url);
+    let headers = match provider.headers(&auth) {
+        Ok(h) => h,
+        Err(e) => {
+            return (
+                ExitCode::from(2),
+                json!({"ok": false, "provider": provider.name(), "error": e}),
+            );
+        }
+    };
+
+    let client = match http_client() {
+        Ok(c) => c,
+        Err(e) => {
+            return (
+                ExitCode::from(1),
+                json!({"ok": false, "provider": provider.name(), "error": e}),
+            );
+        }
+    };
+
+    let mut http_span = tracer.start("http_request");
+    http_span.set_attribute(KeyValue::new("http.request.method", "GET"));
+    http_span.set_attribute(KeyValue::new("url.full", url.clone()));
+
+    let response = match retry::send_with_retry(retry_config, || {
+        client.get(url.as_str()).headers(headers.clone())
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(e) => {
+            return (
+                ExitCode::from(1),
+                json!({"ok": false, "provider": provider.name(), "error": format!("request failed: {e}")}),
+            );
+        }
+    };
+
+    let status = response.status();
+    http_span.set_attribute(KeyValue::new(
+        "http.response.status_code",
+        status.as_u16() as i64,
+    ));
+    let body_text = match response.text().await {
+        Ok(t) => t,
+        Err(e) => {
+            return (
+                ExitCode::from(1),
+                json!({"ok": false, "provider": provider.name(), "error": format!("failed to read response body: {e}")}),
+            );
+        }
+    };
+    http_span.add_event(
+        "http.response.body",
+        vec![KeyValue::new("body", body_text.clone())],
+    );
+    drop(http_span);
+
+    if !status.is_success() {
+        return (
+            ExitCode::from(1),
+            json!({
+                "ok": false,
+                "provider": provider.name(),
+                "error": format!("HTTP {}", status.as_u16()),
+                "response_body": body_text,
+            }),
+        );
+    }
+
+    let usage_value: Value = match serde_json::from_str(&body_text) {
+        Ok(v) => v,
+        Err(e) => {
+            return (
+                ExitCode::from(1),
+                json!({"ok": false, "provider": provider.name(), "error": format!("failed to parse JSON: {e}")}),
+            );
+        }
+    };
+
+    let summary = provider.summarize(&usage_value);
+    metrics::record_quota_metrics(provider.name(), profile, &summary);
+
+    let out = json!({
+        "ok": true,
+        "provider": provider.name(),
+        "usage": usage_value,
+        "summary": summary,
+    });
+    get_active_span(|span| {
+        span.add_event(
+            "output",
+            vec![KeyValue::new(
+                "json",
+                serde_json::to_string(&out).unwrap_or_default(),
+            )],
+        );
+    });
+
+    (ExitCode::SUCCESS, out)
+}
@@ -0,0 +1,255 @@
+//! Claude `QuotaProvider` implementation: OAuth token stored in
+//! `~/.claude/.credentials.json`, usage served from `/api/oauth/usage`.
+
+use crate::config;
+use crate::provider::{AuthHeaders, QuotaProvider};
+use crate::{is_expiring_soon, now_ms, write_json_atomic};
+use opentelemetry::trace::{Span, Tracer, get_active_span};
+use opentelemetry::{KeyValue, global};
+use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, HeaderMap, USER_AGENT};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// OAuth client id Claude Code uses when exchanging a refresh token.
+const CLAUDE_OAUTH_CLIENT_ID: &str = "9d1c250a-e61b-44d9-88ed-5944d1962f5e";
+const CLAUDE_OAUTH_TOKEN_URL: &str = "https://console.anthropic.com/v1/oauth/token";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct UsageWindow {
+    utilization: Option<f64>,
+    resets_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct OAuthUsageResponse {
+    five_hour: Option<UsageWindow>,
+    seven_day: Option<UsageWindow>,
+    seven_day_sonnet: Option<UsageWindow>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ClaudeCredentialsFile {
+    #[serde(rename = "claudeAiOauth")]
+    claude_ai_oauth: Option<ClaudeAiOauth>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ClaudeAiOauth {
+    #[serde(rename = "accessToken")]
+    access_token: Option<String>,
+    #[serde(rename = "refreshToken")]
+    refresh_token: Option<String>,
+    /// Epoch milliseconds the access token expires at.
+    #[serde(rename = "expiresAt")]
+    expires_at: Option<i64>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeRefreshResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+fn left(v: Option<f64>) -> Option<f64> {
+    v.map(|n| (100.0 - n).max(0.0))
+}
+
+async fn refresh_claude_token(refresh_token: &str) -> Result<ClaudeRefreshResponse, String> {
+    let tracer = global::tracer("llm-quota");
+    let mut span = tracer.start("refresh_token");
+    span.set_attribute(KeyValue::new("llm_quota.provider", "claude"));
+    span.set_attribute(KeyValue::new("url.full", CLAUDE_OAUTH_TOKEN_URL));
+
+    let client = crate::provider::http_client()?;
+    let result = client
+        .post(CLAUDE_OAUTH_TOKEN_URL)
+        .header(CONTENT_TYPE, "application/json")
+        .json(&json!({
+            "grant_type": "refresh_token",
+            "refresh_token": refresh_token,
+            "client_id": CLAUDE_OAUTH_CLIENT_ID,
+        }))
+        .send()
+        .await;
+
+    let response = result.map_err(|e| format!("refresh request failed: {e}"))?;
+    let status = response.status();
+    span.set_attribute(KeyValue::new(
+        "http.response.status_code",
+        status.as_u16() as i64,
+    ));
+    let body_text = response
+        .text()
+        .await
+        .map_err(|e| format!("failed to read refresh response: {e}"))?;
+    if !status.is_success() {
+        return Err(format!("refresh HTTP {}: {body_text}", status.as_u16()));
+    }
+    serde_json::from_str(&body_text).map_err(|e| format!("failed to parse refresh response: {e}"))
+}
+
+async fn read_claude_oauth_token(
+    no_refresh: bool,
+    credentials_path_override: Option<&std::path::Path>,
+) -> Result<String, String> {
+    if let Ok(v) = env::var("ANTHROPIC_OAUTH_API_KEY") {
+        let token = v.trim().to_string();
+        if !token.is_empty() {
+            return Ok(token);
+        }
+    }
+
+    let credentials_path = credentials_path_override
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            PathBuf::from(env::var("HOME").unwrap_or_else(|_| "/tmp".to_string()))
+                .join(".claude/.credentials.json")
+        });
+
+    let content = fs::read_to_string(&credentials_path)
+        .map_err(|e| format!("failed to read {}: {e}", credentials_path.display()))?;
+    let mut credentials: ClaudeCredentialsFile = serde_json::from_str(&content)
+        .map_err(|e| format!("failed to parse {}: {e}", credentials_path.display()))?;
+
+    if !no_refresh {
+        if let Some(oauth) = credentials.claude_ai_oauth.as_mut() {
+            let needs_refresh = oauth.expires_at.map(is_expiring_soon).unwrap_or(false);
+            if needs_refresh {
+                if let Some(refresh_token) = oauth.refresh_token.clone() {
+                    match refresh_claude_token(&refresh_token).await {
+                        Ok(refreshed) => {
+                            oauth.access_token = Some(refreshed.access_token);
+                            if let Some(rt) = refreshed.refresh_token {
+                                oauth.refresh_token = Some(rt);
+                            }
+                            if let Some(expires_in) = refreshed.expires_in {
+                                oauth.expires_at = Some(now_ms() + expires_in * 1000);
+                            }
+                            write_json_atomic(&credentials_path, &credentials)?;
+                        }
+                        Err(e) => {
+                            eprintln!(
+                                "llm-quota: warning: failed to refresh Claude OAuth token, falling back to stored access token: {e}"
+                            );
+                            get_active_span(|span| {
+                                span.add_event("refresh_failed", vec![KeyValue::new("error", e)]);
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    credentials
+        .claude_ai_oauth
+        .and_then(|o| o.access_token)
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| {
+            format!(
+                "ANTHROPIC_OAUTH_API_KEY is not set and accessToken was not found in {}",
+                credentials_path.display()
+            )
+        })
+}
+
+/// Claude quota backend: `~/.claude/.credentials.json` + `/api/oauth/usage`.
+pub struct ClaudeProvider {
+    pub no_refresh: bool,
+    pub base_url_override: Option<String>,
+    pub credentials_path_override: Option<PathBuf>,
+}
+
+impl ClaudeProvider {
+    pub fn new(no_refresh: bool, profile: Option<&config::Profile>) -> Self {
+        Self {
+            no_refresh,
+            base_url_override: profile.and_then(|p| p.base_url.clone()),
+            credentials_path_override: profile.and_then(|p| p.credentials_path.clone()),
+        }
+    }
+}
+
+impl QuotaProvider for ClaudeProvider {
+    fn name(&self) -> &'static str {
+        "claude"
+    }
+
+    async fn auth(&self) -> Result<AuthHeaders, String> {
+        let access_token =
+            read_claude_oauth_token(self.no_refresh, self.credentials_path_override.as_deref())
+                .await?;
+        Ok(AuthHeaders {
+            access_token,
+            account_id: None,
+        })
+    }
+
+    fn base_url(&self) -> String {
+        self.base_url_override
+            .clone()
+            .or_else(|| env::var("ANTHROPIC_BASE_URL").ok())
+            .unwrap_or_else(|| "https://api.anthropic.com".to_string())
+    }
+
+    fn request_url(&self, base_url: &str) -> String {
+        format!("{}/api/oauth/usage", base_url.trim_end_matches('/'))
+    }
+
+    fn headers(&self, auth: &AuthHeaders) -> Result<HeaderMap, String> {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, "application/json, text/plain, */*".parse().unwrap());
+        headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+        headers.insert(USER_AGENT, "claude-code/2.0.32".parse().unwrap());
+        headers.insert(
+            AUTHORIZATION,
+            format!("Bearer {}", auth.access_token)
+                .parse()
+                .map_err(|e| format!("access token is not a valid header value: {e}"))?,
+        );
+        headers.insert("anthropic-beta", "oauth-2025-04-20".parse().unwrap());
+        Ok(headers)
+    }
+
+    fn summarize(&self, usage: &Value) -> Map<String, Value> {
+        let usage: OAuthUsageResponse =
+            serde_json::from_value(usage.clone()).unwrap_or(OAuthUsageResponse {
+                five_hour: None,
+                seven_day: None,
+                seven_day_sonnet: None,
+            });
+
+        let mut summary: Map<String, Value> = Map::new();
+        summary.insert(
+            "five_hour".to_string(),
+            json!({
+                "resets_at": usage.five_hour.as_ref().and_then(|w| w.resets_at.clone()),
+                "percent_left": left(usage.five_hour.as_ref().and_then(|w| w.utilization)),
+            }),
+        );
+        summary.insert(
+            "seven_day".to_string(),
+            json!({
+                "resets_at": usage.seven_day.as_ref().and_then(|w| w.resets_at.clone()),
+                "percent_left": left(usage.seven_day.as_ref().and_then(|w| w.utilization)),
+            }),
+        );
+        summary.insert(
+            "seven_day_sonnet".to_string(),
+            json!({
+                "resets_at": usage.seven_day_sonnet.as_ref().and_then(|w| w.resets_at.clone()),
+                "percent_left": left(usage.seven_day_sonnet.as_ref().and_then(|w| w.utilization)),
+            }),
+        );
+        summary
+    }
+}
@@ -0,0 +1,244 @@
+//! Codex `QuotaProvider` implementation: OAuth token stored in
+//! `~/.codex/auth.json`, usage served from `/backend-api/wham/usage`.
+
+use crate::config;
+use crate::provider::{AuthHeaders, QuotaProvider};
+use crate::{is_expiring_soon, now_ms, write_json_atomic};
+use opentelemetry::trace::{Span, Tracer, get_active_span};
+use opentelemetry::{KeyValue, global};
+use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, HeaderMap, USER_AGENT};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// OAuth client id the Codex CLI uses when exchanging a refresh token.
+const CODEX_OAUTH_CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
+const CODEX_OAUTH_TOKEN_URL: &str = "https://auth.openai.com/oauth/token";
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CodexAuthFile {
+    tokens: Option<CodexTokens>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CodexTokens {
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    account_id: Option<String>,
+    /// Epoch milliseconds the access token expires at, when known.
+    expires_at: Option<i64>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CodexRefreshResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+async fn refresh_codex_token(refresh_token: &str) -> Result<CodexRefreshResponse, String> {
+    let tracer = global::tracer("llm-quota");
+    let mut span = tracer.start("refresh_token");
+    span.set_attribute(KeyValue::new("llm_quota.provider", "codex"));
+    span.set_attribute(KeyValue::new("url.full", CODEX_OAUTH_TOKEN_URL));
+
+    let client = crate::provider::http_client()?;
+    let result = client
+        .post(CODEX_OAUTH_TOKEN_URL)
+        .header(CONTENT_TYPE, "application/json")
+        .json(&json!({
+            "grant_type": "refresh_token",
+            "refresh_token": refresh_token,
+            "client_id": CODEX_OAUTH_CLIENT_ID,
+        }))
+        .send()
+        .await;
+
+    let response = result.map_err(|e| format!("refresh request failed: {e}"))?;
+    let status = response.status();
+    span.set_attribute(KeyValue::new(
+        "http.response.status_code",
+        status.as_u16() as i64,
+    ));
+    let body_text = response
+        .text()
+        .await
+        .map_err(|e| format!("failed to read refresh response: {e}"))?;
+    if !status.is_success() {
+        return Err(format!("refresh HTTP {}: {body_text}", status.as_u16()));
+    }
+    serde_json::from_str(&body_text).map_err(|e| format!("failed to parse refresh response: {e}"))
+}
+
+async fn read_codex_auth(
+    no_refresh: bool,
+    auth_path_override: Option<&std::path::Path>,
+) -> Result<(String, String), String> {
+    if let Ok(access_token) = env::var("OPENAI_OAUTH_API_KEY") {
+        let access_token = access_token.trim().to_string();
+        if !access_token.is_empty() {
+            let account_id = env::var("OPENAI_ACCOUNT_ID")
+                .or_else(|_| env::var("CHATGPT_ACCOUNT_ID"))
+                .map_err(|_| {
+                    "OPENAI_OAUTH_API_KEY is set, but OPENAI_ACCOUNT_ID or CHATGPT_ACCOUNT_ID is missing"
+                        .to_string()
+                })?;
+            let account_id = account_id.trim().to_string();
+            if account_id.is_empty() {
+                return Err(
+                    "OPENAI_OAUTH_API_KEY is set, but OPENAI_ACCOUNT_ID/CHATGPT_ACCOUNT_ID is empty"
+                        .to_string(),
+                );
+            }
+            return Ok((access_token, account_id));
+        }
+    }
+
+    let auth_path = auth_path_override.map(PathBuf::from).unwrap_or_else(|| {
+        PathBuf::from(env::var("HOME").unwrap_or_else(|_| "/tmp".to_string()))
+            .join(".codex/auth.json")
+    });
+
+    let content = fs::read_to_string(&auth_path)
+        .map_err(|e| format!("failed to read auth file {}: {e}", auth_path.display()))?;
+    let mut auth: CodexAuthFile = serde_json::from_str(&content)
+        .map_err(|e| format!("failed to parse auth file {}: {e}", auth_path.display()))?;
+
+    if !no_refresh {
+        if let Some(tokens) = auth.tokens.as_mut() {
+            let needs_refresh = tokens.expires_at.map(is_expiring_soon).unwrap_or(false);
+            if needs_refresh {
+                if let Some(refresh_token) = tokens.refresh_token.clone() {
+                    match refresh_codex_token(&refresh_token).await {
+                        Ok(refreshed) => {
+                            tokens.access_token = Some(refreshed.access_token);
+                            if let Some(rt) = refreshed.refresh_token {
+                                tokens.refresh_token = Some(rt);
+                            }
+                            if let Some(expires_in) = refreshed.expires_in {
+                                tokens.expires_at = Some(now_ms() + expires_in * 1000);
+                            }
+                            write_json_atomic(&auth_path, &auth)?;
+                        }
+                        Err(e) => {
+                            eprintln!(
+                                "llm-quota: warning: failed to refresh Codex OAuth token, falling back to stored access token: {e}"
+                            );
+                            get_active_span(|span| {
+                                span.add_event("refresh_failed", vec![KeyValue::new("error", e)]);
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let tokens = auth
+        .tokens
+        .ok_or_else(|| "tokens are missing in codex auth file".to_string())?;
+    let access_token = tokens
+        .access_token
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| "access_token is missing in codex auth file".to_string())?;
+    let account_id = tokens
+        .account_id
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| "account_id is missing in codex auth file".to_string())?;
+
+    Ok((access_token, account_id))
+}
+
+/// Codex quota backend: `~/.codex/auth.json` + `/backend-api/wham/usage`.
+pub struct CodexProvider {
+    pub no_refresh: bool,
+    pub base_url_override: Option<String>,
+    pub credentials_path_override: Option<PathBuf>,
+    pub account_id_override: Option<String>,
+}
+
+impl CodexProvider {
+    pub fn new(no_refresh: bool, profile: Option<&config::Profile>) -> Self {
+        Self {
+            no_refresh,
+            base_url_override: profile.and_then(|p| p.base_url.clone()),
+            credentials_path_override: profile.and_then(|p| p.credentials_path.clone()),
+            account_id_override: profile.and_then(|p| p.account_id.clone()),
+        }
+    }
+}
+
+impl QuotaProvider for CodexProvider {
+    fn name(&self) -> &'static str {
+        "codex"
+    }
+
+    async fn auth(&self) -> Result<AuthHeaders, String> {
+        let (access_token, mut account_id) =
+            read_codex_auth(self.no_refresh, self.credentials_path_override.as_deref()).await?;
+        if let Some(id) = self.account_id_override.clone() {
+            account_id = id;
+        }
+        Ok(AuthHeaders {
+            access_token,
+            account_id: Some(account_id),
+        })
+    }
+
+    fn base_url(&self) -> String {
+        self.base_url_override
+            .clone()
+            .or_else(|| env::var("CHATGPT_BASE_URL").ok())
+            .unwrap_or_else(|| "https://chatgpt.com".to_string())
+    }
+
+    fn request_url(&self, base_url: &str) -> String {
+        format!("{}/backend-api/wham/usage", base_url.trim_end_matches('/'))
+    }
+
+    fn headers(&self, auth: &AuthHeaders) -> Result<HeaderMap, String> {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, "application/json".parse().unwrap());
+        headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+        headers.insert(USER_AGENT, "codex-cli".parse().unwrap());
+        headers.insert(
+            AUTHORIZATION,
+            format!("Bearer {}", auth.access_token)
+                .parse()
+                .map_err(|e| format!("access token is not a valid header value: {e}"))?,
+        );
+        if let Some(account_id) = &auth.account_id {
+            headers.insert(
+                "ChatGPT-Account-Id",
+                account_id
+                    .parse()
+                    .map_err(|e| format!("account id is not a valid header value: {e}"))?,
+            );
+        }
+        Ok(headers)
+    }
+
+    fn summarize(&self, usage: &Value) -> Map<String, Value> {
+        let primary = usage
+            .get("rate_limit")
+            .and_then(|v| v.get("primary_window"))
+            .cloned()
+            .unwrap_or(Value::Null);
+        let secondary = usage
+            .get("rate_limit")
+            .and_then(|v| v.get("secondary_window"))
+            .cloned()
+            .unwrap_or(Value::Null);
+
+        let mut summary: Map<String, Value> = Map::new();
+        summary.insert("five_hour".to_string(), primary);
+        summary.insert("seven_day".to_string(), secondary);
+        summary
+    }
+}
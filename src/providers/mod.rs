@@ -0,0 +1,5 @@
+//! Concrete [`crate::provider::QuotaProvider`] implementations, one module
+//! per backend.
+
+pub mod claude;
+pub mod codex;
@@ -0,0 +1,114 @@
+//! Retry support for transient HTTP failures.
+//!
+//! Wraps a request closure with retries on connection errors and retryable
+//! HTTP statuses (429/500/502/503/504), honoring a `Retry-After` response
+//! header when present and otherwise backing off exponentially with jitter.
+
+use opentelemetry::trace::{Span, Tracer};
+use opentelemetry::{KeyValue, global};
+use reqwest::StatusCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const BACKOFF_CAP_MS: u64 = 30_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            base_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 429 | 500 | 502 | 503 | 504)
+}
+
+fn retry_after_delay(response: &reqwest::Response) -> Option<Duration> {
+    let value = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?;
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = httpdate::parse_http_date(value).ok()?;
+    at.duration_since(SystemTime::now()).ok()
+}
+
+/// A dependency-free jitter source: we only need to perturb a sleep
+/// duration, not generate cryptographic randomness.
+fn jitter_ms(seed: u32) -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    nanos.wrapping_mul(2_654_435_761).wrapping_add(seed as u64)
+}
+
+fn backoff_delay(config: &RetryConfig, attempt: u32) -> Duration {
+    let exp_ms = config
+        .base_delay
+        .as_millis()
+        .saturating_mul(1u128 << attempt.min(16)) as u64;
+    let capped_ms = exp_ms.min(BACKOFF_CAP_MS);
+    let jittered_ms = capped_ms / 2 + jitter_ms(attempt) % (capped_ms / 2 + 1);
+    Duration::from_millis(jittered_ms)
+}
+
+/// Run `make_request` (invoked fresh for every attempt, since a sent
+/// [`reqwest::RequestBuilder`] can't be reused), retrying connection errors
+/// and retryable HTTP statuses up to `config.max_retries` times. Each
+/// attempt is recorded as its own `http_attempt` child span.
+pub async fn send_with_retry<F>(
+    config: &RetryConfig,
+    make_request: F,
+) -> Result<reqwest::Response, reqwest::Error>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+{
+    let tracer = global::tracer("llm-quota");
+    let mut attempt = 0u32;
+    loop {
+        let mut span = tracer.start("http_attempt");
+        span.set_attribute(KeyValue::new("http.request.resend_count", attempt as i64));
+
+        let result = make_request().send().await;
+        let retry_delay = match &result {
+            Ok(response) => {
+                let status = response.status();
+                span.set_attribute(KeyValue::new(
+                    "http.response.status_code",
+                    status.as_u16() as i64,
+                ));
+                if is_retryable_status(status) && attempt < config.max_retries {
+                    Some(retry_after_delay(response).unwrap_or_else(|| backoff_delay(config, attempt)))
+                } else {
+                    None
+                }
+            }
+            Err(e) => {
+                span.set_attribute(KeyValue::new("error", true));
+                if (e.is_connect() || e.is_timeout()) && attempt < config.max_retries {
+                    Some(backoff_delay(config, attempt))
+                } else {
+                    None
+                }
+            }
+        };
+        drop(span);
+
+        let Some(delay) = retry_delay else {
+            return result;
+        };
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}